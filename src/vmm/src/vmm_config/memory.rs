@@ -1,5 +1,8 @@
 // Copyright 2022 Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
+use std::fs::{File, OpenOptions};
+use std::os::unix::io::{FromRawFd, RawFd};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use crate::devices::virtio::memory::device::Memory;
 use serde::{Deserialize, Serialize};
@@ -16,9 +19,207 @@ pub enum MemoryConfigError {
     DeviceWithThisIdExists,
     /// Failed to create a memory device.
     CreateFailure(crate::devices::virtio::memory::Error),
+    /// Failed to bind the backing region to host NUMA nodes: {0}
+    NumaBindingError(std::io::Error),
+    /// Two devices request conflicting host NUMA node bindings.
+    DuplicateNumaBinding,
+    /// Failed to set up the backing file for the memory device: {0}
+    BackingFileError(std::io::Error),
+    /// Saved state is incompatible with the configured device geometry.
+    IncompatibleState,
+    /// The device still has blocks plugged into a running guest.
+    DeviceBusy,
+    /// Worker affinity refers to CPU {0}, which is not online.
+    InvalidCpuAffinity(u16),
+    /// The device is anonymously backed and cannot export its region as an fd.
+    ExportUnsupported,
+    /// Invalid resize to {requested} bytes (must be a multiple of {block_size} and at most {max}).
+    InvalidResize {
+        /// The requested size in bytes.
+        requested: u64,
+        /// The device's block size in bytes.
+        block_size: u64,
+        /// The device's region size, i.e. the maximum allowed size.
+        max: u64,
+    },
 }
 
 type Result<T> = std::result::Result<T, MemoryConfigError>;
+/// The host-side NUMA binding policy applied to a device's backing region.
+///
+/// These map onto the `set_mempolicy(2)`/`mbind(2)` policies: `bind` fails the
+/// allocation when the requested nodes are full, `preferred` silently falls
+/// back to other nodes, and `interleave` round-robins pages over the node mask.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumaBindingPolicy {
+    /// Allocate strictly from the given nodes, failing otherwise.
+    #[default]
+    Bind,
+    /// Prefer the given nodes but fall back silently when they are full.
+    Preferred,
+    /// Round-robin pages across the given node mask.
+    Interleave,
+}
+impl NumaBindingPolicy {
+    /// The `linux/mempolicy.h` mode constant for this policy.
+    fn mode(self) -> i32 {
+        match self {
+            NumaBindingPolicy::Bind => libc::MPOL_BIND,
+            NumaBindingPolicy::Preferred => libc::MPOL_PREFERRED,
+            NumaBindingPolicy::Interleave => libc::MPOL_INTERLEAVE,
+        }
+    }
+}
+/// Binds `[base, base + len)` to `nodes` using `policy` via `mbind(2)`.
+///
+/// The node list is folded into a `u64` bitset of host node indices, matching
+/// the single-word `nodemask` the kernel expects for the first 64 nodes.
+fn bind_region(base: u64, len: u64, nodes: &[u16], policy: NumaBindingPolicy) -> Result<()> {
+    let mut mask: u64 = 0;
+    for &node in nodes {
+        // The single-word mask can only address the first 64 host nodes.
+        if u32::from(node) >= u64::BITS {
+            return Err(MemoryConfigError::NumaBindingError(
+                std::io::Error::from_raw_os_error(libc::EINVAL),
+            ));
+        }
+        mask |= 1u64 << node;
+    }
+    // SAFETY: `base`/`len` describe the device's own backing region and the
+    // nodemask is a stack-local `u64` whose width (64) we pass as `maxnode`.
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_mbind,
+            base as *mut libc::c_void,
+            len as usize,
+            policy.mode(),
+            &mask as *const u64,
+            u64::BITS as u64,
+            0,
+        )
+    };
+    if ret < 0 {
+        return Err(MemoryConfigError::NumaBindingError(
+            std::io::Error::last_os_error(),
+        ));
+    }
+    Ok(())
+}
+/// The huge-page size a device's backing region can be mapped with.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HugePageSize {
+    /// 2 MiB huge pages.
+    #[serde(rename = "2M")]
+    Size2M,
+    /// 1 GiB huge pages.
+    #[serde(rename = "1G")]
+    Size1G,
+}
+impl HugePageSize {
+    /// The page size in bytes.
+    fn bytes(self) -> u64 {
+        match self {
+            HugePageSize::Size2M => 2 << 20,
+            HugePageSize::Size1G => 1 << 30,
+        }
+    }
+    /// The `MAP_HUGE_*`/`MFD_HUGETLB` log2 size shift the kernel expects.
+    fn log2_flag(self) -> libc::c_int {
+        // The kernel encodes the page size as its log2 in bits 26..32.
+        (self.bytes().trailing_zeros() as libc::c_int) << libc::MAP_HUGE_SHIFT
+    }
+}
+/// A backing region opened for a memory device, handed to the device so it can
+/// `mmap` the guest-visible region from it instead of anonymous memory.
+#[derive(Debug)]
+pub struct BackingFile {
+    /// The open backing file or `memfd`.
+    pub file: File,
+    /// Whether the mapping should be `MAP_SHARED`.
+    pub shared: bool,
+    /// The huge-page size the region is backed by, if any.
+    pub huge_pages: Option<HugePageSize>,
+}
+/// A backing region exported as an OS file descriptor, along with the offset
+/// and length an out-of-process backend should map.
+#[derive(Debug)]
+pub struct ExportedRegion {
+    /// A duplicated, read/write fd referring to the device's backing `memfd`.
+    pub fd: RawFd,
+    /// Offset of the guest-visible region within the fd.
+    pub offset: u64,
+    /// Length of the guest-visible region.
+    pub len: u64,
+}
+/// Opens (or creates) the backing store for a device and sizes it to
+/// `region_size`, validating huge-page alignment of both sizes.
+fn open_backing(
+    backing_file: Option<&PathBuf>,
+    shared: bool,
+    huge_pages: Option<HugePageSize>,
+    block_size: u64,
+    region_size: u64,
+) -> Result<BackingFile> {
+    if let Some(hp) = huge_pages {
+        let page = hp.bytes();
+        if region_size % page != 0 || block_size % page != 0 {
+            return Err(MemoryConfigError::BackingFileError(
+                std::io::Error::from_raw_os_error(libc::EINVAL),
+            ));
+        }
+    }
+    let file = match backing_file {
+        Some(path) => OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(path)
+            .map_err(MemoryConfigError::BackingFileError)?,
+        None => {
+            // No path: back the region with an (optionally huge-page) memfd.
+            // `MFD_ALLOW_SEALING` lets `export_fd` seal the region before
+            // handing it to an out-of-process consumer.
+            let mut flags = libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING;
+            if huge_pages.is_some() {
+                flags |= libc::MFD_HUGETLB;
+            }
+            let flags = flags as libc::c_uint
+                | huge_pages.map_or(0, |hp| hp.log2_flag() as libc::c_uint);
+            let name = b"memory_backing\0";
+            // SAFETY: `name` is a valid NUL-terminated C string and the returned
+            // fd is owned exclusively by the `File` we wrap it in.
+            let fd = unsafe { libc::memfd_create(name.as_ptr().cast(), flags) };
+            if fd < 0 {
+                return Err(MemoryConfigError::BackingFileError(
+                    std::io::Error::last_os_error(),
+                ));
+            }
+            // SAFETY: `fd` is a fresh, owned file descriptor from `memfd_create`.
+            unsafe { File::from_raw_fd(fd as RawFd) }
+        }
+    };
+    file.set_len(region_size)
+        .map_err(MemoryConfigError::BackingFileError)?;
+    Ok(BackingFile {
+        file,
+        shared,
+        huge_pages,
+    })
+}
+/// Validates that every CPU index in `cpus` refers to an online host CPU.
+fn validate_affinity(cpus: &[u16]) -> Result<()> {
+    // SAFETY: `sysconf` with a valid name has no preconditions.
+    let online = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    let online = if online < 0 { 0 } else { online as u64 };
+    for &cpu in cpus {
+        if u64::from(cpu) >= online {
+            return Err(MemoryConfigError::InvalidCpuAffinity(cpu));
+        }
+    }
+    Ok(())
+}
 /// This struct represents the strongly typed equivalent of the json body
 /// from memory related requests.
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
@@ -32,6 +233,24 @@ pub struct MemoryDeviceConfig {
     /// Node id if any.
     #[serde(default)]
     pub node_id: u16,
+    /// Host NUMA nodes the backing region should be bound to, if any.
+    #[serde(default)]
+    pub host_numa_nodes: Option<Vec<u16>>,
+    /// Policy used to bind the backing region to `host_numa_nodes`.
+    #[serde(default)]
+    pub binding_policy: NumaBindingPolicy,
+    /// Path of a file to back the region with instead of anonymous memory.
+    #[serde(default)]
+    pub backing_file: Option<PathBuf>,
+    /// Whether the backing region is mapped `MAP_SHARED`.
+    #[serde(default)]
+    pub shared: bool,
+    /// Huge-page size the backing region should use, if any.
+    #[serde(default)]
+    pub huge_pages: Option<HugePageSize>,
+    /// Host CPUs the device's background worker thread should be pinned to.
+    #[serde(default)]
+    pub worker_affinity: Option<Vec<u16>>,
     /// Region size in bytes.
     pub region_size: u64,
     /// Requested size in bytes.
@@ -46,6 +265,22 @@ pub struct MemoryUpdateConfig {
     /// Requested size in bytes.
     pub requested_size: u64,
 }
+/// The persisted state of a single virtio-mem device, captured at snapshot
+/// time and replayed on restore so the guest resumes with the same blocks
+/// plugged.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct MemoryDeviceState {
+    /// ID of the device.
+    pub id: String,
+    /// Block size in bytes.
+    pub block_size: u64,
+    /// Region size in bytes.
+    pub region_size: u64,
+    /// Node id, or 0 when the device is not bound to a guest NUMA node.
+    pub node_id: u16,
+    /// Compact bitmap of plugged blocks, one bit per block, LSB first.
+    pub plugged: Vec<u64>,
+}
 /// A builder for `Memory` devices from 'MemoryDeviceConfig'.
 #[derive(Debug)]
 pub struct MemoryBuilder {
@@ -77,26 +312,239 @@ impl MemoryBuilder {
             cfg.id,
         )
         .map_err(MemoryConfigError::CreateFailure)?;
+        // When a backing file or huge pages are requested, map the region from
+        // that store before exposing any block to the guest.
+        if cfg.backing_file.is_some() || cfg.huge_pages.is_some() || cfg.shared {
+            let backing = open_backing(
+                cfg.backing_file.as_ref(),
+                cfg.shared,
+                cfg.huge_pages,
+                cfg.block_size,
+                cfg.region_size,
+            )?;
+            memory
+                .set_backing(backing)
+                .map_err(MemoryConfigError::CreateFailure)?;
+        }
+        // Bind the freshly-created backing region to the requested host NUMA
+        // nodes before any block is exposed to the guest, so hot-plugged pages
+        // land on the intended nodes.
+        if let Some(nodes) = cfg.host_numa_nodes.as_deref() {
+            if !nodes.is_empty() {
+                bind_region(
+                    memory.region_host_addr(),
+                    memory.region_size(),
+                    nodes,
+                    cfg.binding_policy,
+                )?;
+                memory.set_host_numa_nodes(nodes.to_vec());
+            }
+        }
+        // Validate and record the worker-thread CPU affinity so it can be
+        // applied with `sched_setaffinity` when the device is activated.
+        if let Some(cpus) = cfg.worker_affinity.as_deref() {
+            if !cpus.is_empty() {
+                validate_affinity(cpus)?;
+                memory.set_worker_affinity(cpus.to_vec());
+            }
+        }
         Ok(Arc::new(Mutex::new(memory)))
     }
     /// Inserts into the builder the memory device created from the config.
     pub fn insert(&mut self, cfg: MemoryDeviceConfig) -> Result<()> {
+        // Reject a conflicting host NUMA binding up front, before `build`
+        // applies the `mbind` to the freshly-mmap'd region.
+        if let Some(nodes) = cfg.host_numa_nodes.as_deref() {
+            for device in &self.memory_devices {
+                let existing = device.lock().expect("Poisoned lock");
+                if nodes
+                    .iter()
+                    .any(|node| existing.host_numa_nodes().contains(node))
+                {
+                    return Err(MemoryConfigError::DuplicateNumaBinding);
+                }
+            }
+        }
         let memory = Self::build(cfg)?;
         self.add_device(memory)?;
         Ok(())
     }
     /// Inserts an existing memory device.
     pub fn add_device(&mut self, memory: MutexMemory) -> Result<()> {
+        let (new_id, new_nodes) = {
+            let new = memory.lock().expect("Poisoned lock");
+            (new.id().to_string(), new.host_numa_nodes())
+        };
         for device in &self.memory_devices {
-            if device.lock().expect("Poisoned lock").id()
-                == memory.lock().expect("Poisoned lock").id()
-            {
+            let existing = device.lock().expect("Poisoned lock");
+            if existing.id() == new_id {
                 return Err(MemoryConfigError::DeviceWithThisIdExists);
             }
+            // A given host NUMA node may only be claimed by a single device so
+            // that zone/node pairings stay unique across the builder.
+            if new_nodes
+                .iter()
+                .any(|node| existing.host_numa_nodes().contains(node))
+            {
+                return Err(MemoryConfigError::DuplicateNumaBinding);
+            }
         }
         self.memory_devices.push(memory);
         Ok(())
     }
+    /// Returns the device registered under `id`.
+    pub fn get(&self, id: &str) -> Result<MutexMemory> {
+        self.memory_devices
+            .iter()
+            .find(|device| device.lock().expect("Poisoned lock").id() == id)
+            .cloned()
+            .ok_or(MemoryConfigError::DeviceNotFound)
+    }
+    /// Returns whether a device is registered under `id`.
+    pub fn contains(&self, id: &str) -> bool {
+        self.memory_devices
+            .iter()
+            .any(|device| device.lock().expect("Poisoned lock").id() == id)
+    }
+    /// Removes the device registered under `id`.
+    ///
+    /// Refuses with [`MemoryConfigError::DeviceBusy`] while the device still
+    /// has blocks plugged into a running guest, since tearing the region down
+    /// underneath the guest would be unsound.
+    pub fn remove(&mut self, id: &str) -> Result<()> {
+        let index = self
+            .memory_devices
+            .iter()
+            .position(|device| device.lock().expect("Poisoned lock").id() == id)
+            .ok_or(MemoryConfigError::DeviceNotFound)?;
+        if self.memory_devices[index]
+            .lock()
+            .expect("Poisoned lock")
+            .plugged_size()
+            != 0
+        {
+            return Err(MemoryConfigError::DeviceBusy);
+        }
+        self.memory_devices.remove(index);
+        Ok(())
+    }
+    /// Exports the backing region of the device identified by `id` as a sealed,
+    /// shareable file descriptor.
+    ///
+    /// The device must be backed by a `memfd` (see the file-backing path);
+    /// anonymously-backed devices return
+    /// [`MemoryConfigError::ExportUnsupported`]. The returned fd is a
+    /// read/write duplicate that an out-of-process vhost-user backend or a
+    /// sibling VMM can map to reach the exact same guest memory.
+    pub fn export_fd(&self, id: &str) -> Result<ExportedRegion> {
+        let device = self.get(id)?;
+        let memory = device.lock().expect("Poisoned lock");
+        let fd = memory
+            .backing_fd()
+            .ok_or(MemoryConfigError::ExportUnsupported)?;
+        // Seal the region size so the consumer can trust the fd's length.
+        // SAFETY: `fd` is the device's own backing `memfd`.
+        let ret = unsafe {
+            libc::fcntl(
+                fd,
+                libc::F_ADD_SEALS,
+                libc::F_SEAL_SHRINK | libc::F_SEAL_GROW,
+            )
+        };
+        if ret < 0 {
+            return Err(MemoryConfigError::BackingFileError(
+                std::io::Error::last_os_error(),
+            ));
+        }
+        // SAFETY: `fd` is a valid, open descriptor owned by the device.
+        let dup = unsafe { libc::dup(fd) };
+        if dup < 0 {
+            return Err(MemoryConfigError::BackingFileError(
+                std::io::Error::last_os_error(),
+            ));
+        }
+        Ok(ExportedRegion {
+            fd: dup,
+            offset: 0,
+            len: memory.region_size(),
+        })
+    }
+    /// Applies a validated virtio-mem resize to the device identified by `id`
+    /// and returns the size actually reached.
+    ///
+    /// The request is bounded by the configured `region_size`, must be a
+    /// multiple of the device's `block_size`, and may not shrink below the
+    /// bytes currently plugged and in use by the guest; otherwise
+    /// [`MemoryConfigError::InvalidResize`] is returned. The guest only
+    /// unplugs on block boundaries, so the returned size may differ from the
+    /// request.
+    pub fn update(&mut self, id: &str, cfg: MemoryUpdateConfig) -> Result<u64> {
+        let device = self.get(id)?;
+        let mut memory = device.lock().expect("Poisoned lock");
+        let block_size = memory.block_size();
+        let max = memory.region_size();
+        let requested = cfg.requested_size;
+        if requested > max
+            || requested % block_size != 0
+            || requested < memory.used_size()
+        {
+            return Err(MemoryConfigError::InvalidResize {
+                requested,
+                block_size,
+                max,
+            });
+        }
+        memory
+            .resize(requested)
+            .map_err(MemoryConfigError::CreateFailure)
+    }
+    /// Captures the plugged-block state of every device for a snapshot.
+    pub fn save(&self) -> Vec<MemoryDeviceState> {
+        self.memory_devices
+            .iter()
+            .map(|device| {
+                let memory = device.lock().expect("Poisoned lock");
+                MemoryDeviceState {
+                    id: memory.id().to_string(),
+                    block_size: memory.block_size(),
+                    region_size: memory.region_size(),
+                    node_id: memory.node_id().unwrap_or(0),
+                    plugged: memory.plugged_bitmap(),
+                }
+            })
+            .collect()
+    }
+    /// Rebuilds a builder from snapshot `states`, re-plugging exactly the blocks
+    /// recorded in each bitmap.
+    ///
+    /// `backing` supplies the live device configs (region/block sizes, backing
+    /// files, host NUMA binding); each state is matched to its config by id and
+    /// rejected with [`MemoryConfigError::IncompatibleState`] when the geometry
+    /// has changed.
+    pub fn restore(
+        states: Vec<MemoryDeviceState>,
+        backing: Vec<MemoryDeviceConfig>,
+    ) -> Result<Self> {
+        let mut builder = MemoryBuilder::new();
+        for state in states {
+            let cfg = backing
+                .iter()
+                .find(|cfg| cfg.id == state.id)
+                .ok_or(MemoryConfigError::DeviceNotFound)?;
+            if cfg.region_size != state.region_size || cfg.block_size != state.block_size {
+                return Err(MemoryConfigError::IncompatibleState);
+            }
+            // `build` re-establishes the backing store and host NUMA binding.
+            let memory = Self::build(cfg.clone())?;
+            memory
+                .lock()
+                .expect("Poisoned lock")
+                .restore_plugged(&state.plugged)
+                .map_err(MemoryConfigError::CreateFailure)?;
+            builder.add_device(memory)?;
+        }
+        Ok(builder)
+    }
     /// Gets an iterator over mutable references
     pub fn iter_mut(&mut self) -> std::slice::IterMut<MutexMemory> {
         self.memory_devices.iter_mut()
@@ -118,6 +566,12 @@ pub(crate) mod tests {
             id: String::from("memory-dev"),
             block_size: page_size(),
             node_id: 0,
+            host_numa_nodes: None,
+            binding_policy: NumaBindingPolicy::Bind,
+            backing_file: None,
+            shared: false,
+            huge_pages: None,
+            worker_affinity: None,
             region_size: 8 * page_size(),
             requested_size: 0,
         }
@@ -127,6 +581,12 @@ pub(crate) mod tests {
             id: String::from("broken-config"),
             block_size: page_size() + 1,
             node_id: 0,
+            host_numa_nodes: None,
+            binding_policy: NumaBindingPolicy::Bind,
+            backing_file: None,
+            shared: false,
+            huge_pages: None,
+            worker_affinity: None,
             region_size: page_size() + 2,
             requested_size: 0,
         }
@@ -153,4 +613,103 @@ pub(crate) mod tests {
         // adding a valid one should work
         assert!(memory_builder.insert(default_config()).is_ok());
     }
-}
\ No newline at end of file
+    #[test]
+    fn test_validate_affinity_rejects_offline_cpu() {
+        // CPU 0 is always online, so the [0] case passes.
+        assert!(validate_affinity(&[0]).is_ok());
+        // u16::MAX can never be an online CPU index on this host.
+        match validate_affinity(&[u16::MAX]) {
+            Err(MemoryConfigError::InvalidCpuAffinity(cpu)) => assert_eq!(cpu, u16::MAX),
+            _ => unreachable!(),
+        }
+    }
+    #[test]
+    fn test_export_fd_rejects_anonymous() {
+        let mut memory_builder = MemoryBuilder::new();
+        let cfg = default_config();
+        let id = cfg.id.clone();
+        // default_config is anonymously backed (no backing_file/huge_pages)
+        assert!(memory_builder.insert(cfg).is_ok());
+        match memory_builder.export_fd(&id) {
+            Err(MemoryConfigError::ExportUnsupported) => {}
+            _ => unreachable!(),
+        }
+    }
+    #[test]
+    fn test_get_contains_remove() {
+        let mut memory_builder = MemoryBuilder::new();
+        let cfg = default_config();
+        let id = cfg.id.clone();
+        assert!(memory_builder.insert(cfg).is_ok());
+        assert!(memory_builder.contains(&id));
+        assert!(memory_builder.get(&id).is_ok());
+        // an unknown id is reported as not found
+        assert!(!memory_builder.contains("nope"));
+        match memory_builder.get("nope") {
+            Err(MemoryConfigError::DeviceNotFound) => {}
+            _ => unreachable!(),
+        }
+        // a freshly-built device has no plugged blocks, so removal succeeds
+        assert!(memory_builder.remove(&id).is_ok());
+        assert!(!memory_builder.contains(&id));
+    }
+    #[test]
+    fn test_update_rejects_unaligned_resize() {
+        let mut memory_builder = MemoryBuilder::new();
+        let cfg = default_config();
+        let id = cfg.id.clone();
+        assert!(memory_builder.insert(cfg).is_ok());
+        // a size that is not a multiple of the block size must be rejected
+        let update = MemoryUpdateConfig {
+            requested_size: page_size() + 1,
+        };
+        match memory_builder.update(&id, update) {
+            Err(MemoryConfigError::InvalidResize { block_size, .. }) => {
+                assert_eq!(block_size, page_size());
+            }
+            _ => unreachable!(),
+        }
+    }
+    #[test]
+    fn test_restore_incompatible_state() {
+        let cfg = default_config();
+        let state = MemoryDeviceState {
+            id: cfg.id.clone(),
+            // a block size that no longer matches the live config
+            block_size: cfg.block_size * 2,
+            region_size: cfg.region_size,
+            node_id: 0,
+            plugged: vec![0],
+        };
+        match MemoryBuilder::restore(vec![state], vec![cfg]) {
+            Err(MemoryConfigError::IncompatibleState) => {}
+            _ => unreachable!(),
+        }
+    }
+    #[test]
+    fn test_backing_rejects_unaligned_huge_pages() {
+        // A region that is not a multiple of the huge-page size must be rejected
+        // before any file/memfd is created.
+        match open_backing(None, false, Some(HugePageSize::Size2M), page_size(), page_size()) {
+            Err(MemoryConfigError::BackingFileError(e)) => {
+                assert_eq!(e.raw_os_error(), Some(libc::EINVAL));
+            }
+            _ => unreachable!(),
+        }
+    }
+    #[test]
+    fn test_insert_duplicate_numa_binding() {
+        let mut memory_builder = MemoryBuilder::new();
+        let mut first = default_config();
+        first.host_numa_nodes = Some(vec![0, 1]);
+        assert!(memory_builder.insert(first).is_ok());
+        // a second device claiming an already-bound host node must be rejected
+        let mut second = default_config();
+        second.id = String::from("memory-dev-2");
+        second.host_numa_nodes = Some(vec![1, 2]);
+        match memory_builder.insert(second) {
+            Err(MemoryConfigError::DuplicateNumaBinding) => {}
+            _ => unreachable!(),
+        }
+    }
+}